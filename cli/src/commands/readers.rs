@@ -5,13 +5,117 @@ use gumdrop::Options;
 use std::{
     io::{self, Write},
     process::exit,
+    str::FromStr,
 };
 use termcolor::{ColorSpec, StandardStreamLock, WriteColor};
 use yubikey_piv::{Readers, Serial};
 
+/// Output format for the `readers` subcommand
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Format {
+    /// Colorized, human-readable text (the default)
+    Text,
+    /// A JSON array of reader objects, for scripting/CI consumption
+    Json,
+}
+
+impl Default for Format {
+    fn default() -> Self {
+        Format::Text
+    }
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(Format::Text),
+            "json" => Ok(Format::Json),
+            other => Err(format!("invalid `--format` value `{}` (expected `text` or `json`)", other)),
+        }
+    }
+}
+
+/// Per-reader information gathered for display
+struct ReaderInfo {
+    index: usize,
+    name: String,
+    serial: Option<Serial>,
+    version: Option<String>,
+    form_factor: Option<String>,
+    skip_reason: Option<String>,
+}
+
+impl ReaderInfo {
+    /// Whether this reader yielded a usable YubiKey
+    fn found(&self) -> bool {
+        self.skip_reason.is_none()
+    }
+
+    /// Render this entry as a single JSON object
+    fn to_json(&self) -> String {
+        format!(
+            "{{\"name\":{},\"serial\":{},\"version\":{},\"form_factor\":{},\"skip_reason\":{}}}",
+            json_string(&self.name),
+            self.serial
+                .map(|s| s.to_string())
+                .map(|s| json_string(&s))
+                .unwrap_or_else(|| "null".to_owned()),
+            self.version
+                .as_deref()
+                .map(json_string)
+                .unwrap_or_else(|| "null".to_owned()),
+            self.form_factor
+                .as_deref()
+                .map(json_string)
+                .unwrap_or_else(|| "null".to_owned()),
+            self.skip_reason
+                .as_deref()
+                .map(json_string)
+                .unwrap_or_else(|| "null".to_owned()),
+        )
+    }
+}
+
+/// Escape and quote a string for inclusion in JSON output
+fn json_string(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
 /// The `readers` subcommand
 #[derive(Debug, Options)]
-pub struct ReadersCmd {}
+pub struct ReadersCmd {
+    /// Output format: `text` (default) or `json`
+    #[options(no_short, long = "format", default = "text")]
+    format: Format,
+
+    /// Only show the reader matching this serial number (readers that fail
+    /// to open have no known serial and are not filtered out)
+    #[options(no_short, long = "serial")]
+    serial: Option<Serial>,
+
+    /// Only show readers whose name contains this substring
+    #[options(no_short, long = "name")]
+    name: Option<String>,
+
+    /// In text output, report why a reader was skipped instead of silently
+    /// continuing (JSON output always includes the skip reason)
+    #[options(no_short, long = "verbose")]
+    verbose: bool,
+}
 
 impl ReadersCmd {
     /// Run the `readers` subcommand
@@ -26,23 +130,89 @@ impl ReadersCmd {
             exit(1);
         });
 
-        if readers_iter.len() == 0 {
-            status_err!("no YubiKeys detected!");
-            exit(1);
-        }
-
-        let mut s = STDOUT.lock();
-        s.reset().unwrap();
+        let mut infos = Vec::new();
 
         for (i, reader) in readers_iter.enumerate() {
             let name = reader.name();
+
+            if let Some(filter) = &self.name {
+                if !name.contains(filter.as_str()) {
+                    continue;
+                }
+            }
+
             let mut yubikey = match reader.open() {
                 Ok(yk) => yk,
-                Err(_) => continue,
+                Err(e) => {
+                    infos.push(ReaderInfo {
+                        index: i + 1,
+                        name: name.to_owned(),
+                        serial: None,
+                        version: None,
+                        form_factor: None,
+                        skip_reason: Some(e.to_string()),
+                    });
+                    continue;
+                }
             };
 
             let serial = yubikey.serial();
-            self.print_reader(&mut s, i + 1, &name, serial).unwrap();
+
+            if let Some(filter) = self.serial {
+                if serial != filter {
+                    continue;
+                }
+            }
+
+            let version = yubikey.version().map(|v| v.to_string()).ok();
+            let form_factor = yubikey.form_factor().map(|f| f.to_string()).ok();
+
+            infos.push(ReaderInfo {
+                index: i + 1,
+                name: name.to_owned(),
+                serial: Some(serial),
+                version,
+                form_factor,
+                skip_reason: None,
+            });
+        }
+
+        let found_any = infos.iter().any(ReaderInfo::found);
+
+        match self.format {
+            Format::Json => self.print_json(&infos),
+            Format::Text => self.print_text(&infos),
+        }
+
+        if !found_any {
+            status_err!("no YubiKeys detected!");
+            exit(1);
+        }
+    }
+
+    /// Print the gathered reader info as a JSON array
+    fn print_json(&self, infos: &[ReaderInfo]) {
+        let entries: Vec<String> = infos.iter().map(ReaderInfo::to_json).collect();
+        println!("[{}]", entries.join(","));
+    }
+
+    /// Print the gathered reader info as colorized text
+    fn print_text(&self, infos: &[ReaderInfo]) {
+        let mut s = STDOUT.lock();
+        s.reset().unwrap();
+
+        for info in infos {
+            match &info.skip_reason {
+                Some(reason) => {
+                    if self.verbose {
+                        self.print_skipped(&mut s, info.index, &info.name, reason).unwrap();
+                    }
+                }
+                None => {
+                    self.print_reader(&mut s, info.index, &info.name, info.serial.unwrap())
+                        .unwrap();
+                }
+            }
         }
     }
 
@@ -61,4 +231,20 @@ impl ReadersCmd {
         stream.flush()?;
         Ok(())
     }
+
+    /// Print a reader that was skipped, and why
+    fn print_skipped(
+        &self,
+        stream: &mut StandardStreamLock<'_>,
+        index: usize,
+        name: &str,
+        reason: &str,
+    ) -> Result<(), io::Error> {
+        stream.set_color(ColorSpec::new().set_bold(true).set_fg(Some(termcolor::Color::Yellow)))?;
+        write!(stream, "{:>3}:", index)?;
+        stream.reset()?;
+        writeln!(stream, " {} (skipped: {})", name, reason)?;
+        stream.flush()?;
+        Ok(())
+    }
 }
@@ -33,7 +33,6 @@
 use crate::{error::Error, yubikey::YubiKey};
 use getrandom::getrandom;
 use std::fmt::{self, Debug, Display};
-use subtle_encoding::hex;
 
 /// CCCID size
 pub const CCCID_SIZE: usize = 14;
@@ -41,6 +40,12 @@ pub const CCCID_SIZE: usize = 14;
 /// CCC size
 pub const CCC_SIZE: usize = 51;
 
+/// Manufacturer ID offset
+const MANUFACTURER_ID_OFFS: usize = 7;
+
+/// Card type offset
+const CARD_TYPE_OFFS: usize = 8;
+
 /// CCCID offset
 const CCC_ID_OFFS: usize = 9;
 
@@ -87,13 +92,37 @@ impl CCC {
         Ok(CardId(cccid))
     }
 
-    /// Get Cardholder Capability Container (CCC) ID
-    pub fn get(yubikey: &mut YubiKey) -> Result<Self, Error> {
-        let txn = yubikey.begin_transaction()?;
-        let response = txn.fetch_object(OBJ_CAPABILITY)?;
+    /// Manufacturer ID embedded in the card identifier (`f0`) field
+    pub fn manufacturer_id(&self) -> u8 {
+        self.0[MANUFACTURER_ID_OFFS]
+    }
 
+    /// Card type embedded in the card identifier (`f0`) field
+    pub fn card_type(&self) -> u8 {
+        self.0[CARD_TYPE_OFFS]
+    }
+
+    /// Parse and validate a CCC read off the card against [`CCC_TMPL`]
+    ///
+    /// Only the fixed parts of the template - the `f0` tag/length and its
+    /// GSC-IS RID prefix, and the trailing `f1..fe` tags - are checked; the
+    /// manufacturer ID, card type, and card ID embedded in `f0` vary per
+    /// card and are left unconstrained.
+    fn parse(response: &[u8]) -> Result<Self, Error> {
         if response.len() != CCC_TMPL.len() {
-            return Err(Error::GenericError);
+            return Err(Error::ParseError);
+        }
+
+        if response[0] != CCC_TMPL[0] || response[1] != CCC_TMPL[1] {
+            return Err(Error::ParseError);
+        }
+
+        if response[2..MANUFACTURER_ID_OFFS] != CCC_TMPL[2..MANUFACTURER_ID_OFFS] {
+            return Err(Error::ParseError);
+        }
+
+        if response[(CCC_ID_OFFS + CCCID_SIZE)..] != CCC_TMPL[(CCC_ID_OFFS + CCCID_SIZE)..] {
+            return Err(Error::ParseError);
         }
 
         let mut ccc = [0u8; CCC_SIZE];
@@ -101,6 +130,13 @@ impl CCC {
         Ok(Self(ccc))
     }
 
+    /// Get Cardholder Capability Container (CCC) ID
+    pub fn get(yubikey: &mut YubiKey) -> Result<Self, Error> {
+        let txn = yubikey.begin_transaction()?;
+        let response = txn.fetch_object(OBJ_CAPABILITY)?;
+        Self::parse(&response)
+    }
+
     /// Get Cardholder Capability Container (CCC) ID
     #[cfg(feature = "untested")]
     pub fn set(&self, yubikey: &mut YubiKey) -> Result<(), Error> {
@@ -120,10 +156,65 @@ impl Debug for CCC {
 
 impl Display for CCC {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(
-            f,
-            "{}",
-            String::from_utf8(hex::encode(&self.0[..])).unwrap()
-        )
+        for byte in &self.0 {
+            write!(f, "{:02x}", byte)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_accepts_tmpl_shaped_response() {
+        let ccc = CCC::parse(CCC_TMPL).unwrap();
+        assert_eq!(&ccc.0[..], CCC_TMPL);
+    }
+
+    #[test]
+    fn test_parse_rejects_wrong_length() {
+        assert!(CCC::parse(&CCC_TMPL[..CCC_TMPL.len() - 1]).is_err());
+    }
+
+    #[test]
+    fn test_parse_rejects_bad_rid() {
+        let mut bad = CCC_TMPL.to_vec();
+        bad[2] = 0xff; // corrupt the GSC-IS RID prefix
+        assert!(CCC::parse(&bad).is_err());
+    }
+
+    #[test]
+    fn test_parse_rejects_bad_trailing_tags() {
+        let mut bad = CCC_TMPL.to_vec();
+        let last = bad.len() - 1;
+        bad[last] = 0xff; // corrupt the trailing f1..fe tags
+        assert!(CCC::parse(&bad).is_err());
+    }
+
+    #[test]
+    fn test_parse_allows_varying_card_id_fields() {
+        let mut response = CCC_TMPL.to_vec();
+        response[MANUFACTURER_ID_OFFS] = 0x42;
+        response[CARD_TYPE_OFFS] = 0x99;
+        response[CCC_ID_OFFS..CCC_ID_OFFS + CCCID_SIZE].copy_from_slice(&[0xaa; CCCID_SIZE]);
+
+        let ccc = CCC::parse(&response).unwrap();
+        assert_eq!(ccc.manufacturer_id(), 0x42);
+        assert_eq!(ccc.card_type(), 0x99);
+        assert_eq!(ccc.cccid().unwrap().0, [0xaa; CCCID_SIZE]);
+    }
+
+    #[test]
+    fn test_display_does_not_panic_on_arbitrary_bytes() {
+        let ccc = CCC([0xff; CCC_SIZE]);
+        assert_eq!(format!("{}", ccc), "ff".repeat(CCC_SIZE));
+    }
+
+    #[test]
+    fn test_debug_does_not_panic_on_arbitrary_bytes() {
+        let ccc = CCC([0; CCC_SIZE]);
+        let _ = format!("{:?}", ccc);
     }
 }
@@ -0,0 +1,32 @@
+//! Error types
+
+use std::fmt::{self, Display};
+
+/// Error type
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Error {
+    /// Generic error
+    GenericError,
+
+    /// Couldn't obtain randomness from the OS
+    RandomnessError,
+
+    /// Data read from the card did not match the expected structure
+    ParseError,
+
+    /// The requested data object does not exist on the card
+    ObjectNotFound,
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::GenericError => write!(f, "generic error"),
+            Error::RandomnessError => write!(f, "error generating random data"),
+            Error::ParseError => write!(f, "malformed data"),
+            Error::ObjectNotFound => write!(f, "data object not found"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
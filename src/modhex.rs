@@ -0,0 +1,95 @@
+//! ModHex Encoding
+//!
+//! Yubico's "Modified Hexadecimal" encoding is used throughout the YubiKey
+//! ecosystem - most visibly in OTP public IDs - because its 16-symbol
+//! alphabet only uses characters that appear in the same place on every
+//! keyboard layout, so an OTP typed by the device's virtual keyboard always
+//! comes out right regardless of the host's layout.
+
+use crate::error::Error;
+
+/// The ModHex alphabet, indexed by nibble value
+const ALPHABET: &[u8; 16] = b"cbdefghijklnrtuv";
+
+/// Look up the nibble value of a ModHex symbol
+fn index_of(c: u8) -> Result<u8, Error> {
+    ALPHABET
+        .iter()
+        .position(|&a| a == c)
+        .map(|i| i as u8)
+        .ok_or(Error::GenericError)
+}
+
+/// Encode bytes as a ModHex string
+pub fn encode(data: &[u8]) -> String {
+    let mut out = String::with_capacity(data.len() * 2);
+
+    for &byte in data {
+        out.push(ALPHABET[(byte >> 4) as usize] as char);
+        out.push(ALPHABET[(byte & 0x0f) as usize] as char);
+    }
+
+    out
+}
+
+/// Decode a ModHex string into bytes
+pub fn decode(s: &str) -> Result<Vec<u8>, Error> {
+    if s.len() % 2 != 0 || !s.is_ascii() {
+        return Err(Error::GenericError);
+    }
+
+    let bytes = s.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len() / 2);
+
+    for pair in bytes.chunks_exact(2) {
+        let hi = index_of(pair[0])?;
+        let lo = index_of(pair[1])?;
+        out.push((hi << 4) | lo);
+    }
+
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// (hex, modhex) vectors, taken from Yubico's ModHex reference examples
+    const VECTORS: &[(&str, &str)] = &[
+        ("00000000000000000000000000000000", "cccccccccccccccccccccccccccccccc"),
+        ("0123456789", "cbdefghijk"),
+        ("ffffffffffffffffffffffffffffffff", "vvvvvvvvvvvvvvvvvvvvvvvvvvvvvvvv"),
+    ];
+
+    #[test]
+    fn test_encode() {
+        for (hex, modhex) in VECTORS {
+            let data = subtle_encoding::hex::decode(hex).unwrap();
+            assert_eq!(encode(&data), *modhex);
+        }
+    }
+
+    #[test]
+    fn test_decode() {
+        for (hex, modhex) in VECTORS {
+            let expected = subtle_encoding::hex::decode(hex).unwrap();
+            assert_eq!(decode(modhex).unwrap(), expected);
+        }
+    }
+
+    #[test]
+    fn test_decode_rejects_odd_length() {
+        assert!(decode("c").is_err());
+    }
+
+    #[test]
+    fn test_decode_rejects_invalid_alphabet() {
+        assert!(decode("zz").is_err());
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let data = b"some arbitrary test bytes!!";
+        assert_eq!(decode(&encode(data)).unwrap(), data);
+    }
+}
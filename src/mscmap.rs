@@ -0,0 +1,225 @@
+//! MS Container Map (`mscmap`) Support
+//!
+//! This is the other half of Windows CSP/KSP interoperability alongside
+//! [`crate::cccid`]: a PIV data object holding a packed array of
+//! `CONTAINER_MAP_RECORD`s, one per key container, which is how the
+//! minidriver learns a GUID and signature/exchange key sizes for each PIV
+//! slot in use. Get the byte layout of [`ContainerRecord`] wrong and the
+//! card enumerates with the wrong containers, or not at all.
+
+use crate::{error::Error, piv::SlotId, yubikey::YubiKey};
+
+/// `mscmap` Object ID
+const OBJ_MSCMAP: u32 = 0x005f_ff10;
+
+/// Size in bytes of a single packed `CONTAINER_MAP_RECORD`
+const CONTAINER_RECORD_SIZE: usize = 86;
+
+/// Size in `u16`s (including the trailing NUL) of `wszGuid`
+const GUID_CHARS: usize = 40;
+
+/// `bFlags` bit set when the container holds a valid key pair
+pub const CONTAINER_MAP_VALID_CONTAINER: u8 = 0x01;
+
+/// `bFlags` bit set on the record acting as the default container
+pub const CONTAINER_MAP_DEFAULT_CONTAINER: u8 = 0x02;
+
+/// A single Microsoft Smart Card Minidriver container map record
+///
+/// Mirrors the minidriver's `CONTAINER_MAP_RECORD`: a UTF-16LE GUID
+/// naming the container, a flags byte, and the bit-lengths of the
+/// signature and exchange keys it holds (`0` if the corresponding key
+/// isn't present).
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ContainerRecord {
+    /// Container GUID, as the minidriver's null-terminated UTF-16LE string
+    pub guid: [u16; GUID_CHARS],
+
+    /// Flags (e.g. [`CONTAINER_MAP_DEFAULT_CONTAINER`])
+    pub flags: u8,
+
+    /// Size in bits of the signature key, or `0` if absent
+    pub sig_key_size_bits: u16,
+
+    /// Size in bits of the key exchange key, or `0` if absent
+    pub key_exchange_key_size_bits: u16,
+}
+
+impl ContainerRecord {
+    /// Create a record for the given GUID string (must be ASCII and fit,
+    /// with its NUL terminator, in [`GUID_CHARS`] UTF-16 code units)
+    pub fn new(guid: &str, flags: u8, sig_key_size_bits: u16, key_exchange_key_size_bits: u16) -> Result<Self, Error> {
+        if guid.len() + 1 > GUID_CHARS {
+            return Err(Error::GenericError);
+        }
+
+        let mut wguid = [0u16; GUID_CHARS];
+        for (dst, src) in wguid.iter_mut().zip(guid.encode_utf16()) {
+            *dst = src;
+        }
+
+        Ok(Self {
+            guid: wguid,
+            flags,
+            sig_key_size_bits,
+            key_exchange_key_size_bits,
+        })
+    }
+
+    /// Parse a single packed record
+    fn from_bytes(buf: &[u8]) -> Result<Self, Error> {
+        if buf.len() != CONTAINER_RECORD_SIZE {
+            return Err(Error::GenericError);
+        }
+
+        let mut guid = [0u16; GUID_CHARS];
+        for (i, chunk) in buf[0..GUID_CHARS * 2].chunks_exact(2).enumerate() {
+            guid[i] = u16::from_le_bytes([chunk[0], chunk[1]]);
+        }
+
+        // buf[GUID_CHARS * 2 + 1] is `bReserved`, kept zero to preserve the
+        // minidriver's 2-byte alignment of the `wSigKeySizeBits` /
+        // `wKeyExchangeKeySizeBits` fields that follow it.
+        let flags = buf[GUID_CHARS * 2];
+        let sig_key_size_bits = u16::from_le_bytes([buf[GUID_CHARS * 2 + 2], buf[GUID_CHARS * 2 + 3]]);
+        let key_exchange_key_size_bits =
+            u16::from_le_bytes([buf[GUID_CHARS * 2 + 4], buf[GUID_CHARS * 2 + 5]]);
+
+        Ok(Self {
+            guid,
+            flags,
+            sig_key_size_bits,
+            key_exchange_key_size_bits,
+        })
+    }
+
+    /// Serialize this record to its packed on-card representation
+    fn to_bytes(&self) -> [u8; CONTAINER_RECORD_SIZE] {
+        let mut buf = [0u8; CONTAINER_RECORD_SIZE];
+
+        for (i, word) in self.guid.iter().enumerate() {
+            let bytes = word.to_le_bytes();
+            buf[i * 2] = bytes[0];
+            buf[i * 2 + 1] = bytes[1];
+        }
+
+        buf[GUID_CHARS * 2] = self.flags;
+        // buf[GUID_CHARS * 2 + 1] is `bReserved`, left as zero padding.
+        buf[GUID_CHARS * 2 + 2..GUID_CHARS * 2 + 4].copy_from_slice(&self.sig_key_size_bits.to_le_bytes());
+        buf[GUID_CHARS * 2 + 4..GUID_CHARS * 2 + 6]
+            .copy_from_slice(&self.key_exchange_key_size_bits.to_le_bytes());
+
+        buf
+    }
+
+    /// Size in bits of the key this record holds for the given PIV slot, or
+    /// `None` if it holds none
+    ///
+    /// The minidriver doesn't record a slot ID directly; by convention the
+    /// signature key maps to [`SlotId::Signature`] and the exchange key to
+    /// [`SlotId::KeyManagement`].
+    fn slot_for(&self, slot: SlotId) -> Option<u16> {
+        match slot {
+            SlotId::Signature if self.sig_key_size_bits != 0 => Some(self.sig_key_size_bits),
+            SlotId::KeyManagement if self.key_exchange_key_size_bits != 0 => {
+                Some(self.key_exchange_key_size_bits)
+            }
+            _ => None,
+        }
+    }
+}
+
+/// MS Container Map
+///
+/// The full set of [`ContainerRecord`]s presented to the Windows
+/// minidriver, in on-card order.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct MsContainerMap(pub Vec<ContainerRecord>);
+
+impl MsContainerMap {
+    /// Read the container map off the YubiKey
+    pub fn get(yubikey: &mut YubiKey) -> Result<Self, Error> {
+        let txn = yubikey.begin_transaction()?;
+        let response = txn.fetch_object(OBJ_MSCMAP)?;
+
+        if response.len() % CONTAINER_RECORD_SIZE != 0 {
+            return Err(Error::GenericError);
+        }
+
+        let records = response
+            .chunks_exact(CONTAINER_RECORD_SIZE)
+            .map(ContainerRecord::from_bytes)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Self(records))
+    }
+
+    /// Write the container map to the YubiKey, replacing whatever is there
+    #[cfg(feature = "untested")]
+    pub fn set(&self, yubikey: &mut YubiKey) -> Result<(), Error> {
+        let mut buf = Vec::with_capacity(self.0.len() * CONTAINER_RECORD_SIZE);
+        for record in &self.0 {
+            buf.extend_from_slice(&record.to_bytes());
+        }
+
+        let txn = yubikey.begin_transaction()?;
+        txn.save_object(OBJ_MSCMAP, &buf)
+    }
+
+    /// Add or replace the record naming the container for `slot`, persisting
+    /// the updated map to the YubiKey
+    #[cfg(feature = "untested")]
+    pub fn add_slot(
+        &mut self,
+        yubikey: &mut YubiKey,
+        slot: SlotId,
+        record: ContainerRecord,
+    ) -> Result<(), Error> {
+        match self.0.iter_mut().find(|r| r.slot_for(slot).is_some()) {
+            Some(existing) => *existing = record,
+            None => self.0.push(record),
+        }
+
+        self.set(yubikey)
+    }
+
+    /// Remove the record mapping `slot`, if any, persisting the updated map
+    /// to the YubiKey
+    #[cfg(feature = "untested")]
+    pub fn remove_slot(&mut self, yubikey: &mut YubiKey, slot: SlotId) -> Result<(), Error> {
+        self.0.retain(|r| r.slot_for(slot).is_none());
+        self.set(yubikey)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_round_trips_through_bytes() {
+        let record = ContainerRecord::new("{00000000-0000-0000-0000-000000000001}", CONTAINER_MAP_VALID_CONTAINER, 2048, 1024).unwrap();
+
+        let bytes = record.to_bytes();
+        assert_eq!(bytes.len(), CONTAINER_RECORD_SIZE);
+
+        let parsed = ContainerRecord::from_bytes(&bytes).unwrap();
+        assert_eq!(parsed, record);
+    }
+
+    #[test]
+    fn test_record_reserved_byte_is_zero() {
+        let record = ContainerRecord::new("{guid}", CONTAINER_MAP_DEFAULT_CONTAINER, 0, 0).unwrap();
+        let bytes = record.to_bytes();
+
+        // The byte between `bFlags` and `wSigKeySizeBits` is `bReserved`,
+        // kept zero to hold the 2-byte alignment of the size fields.
+        assert_eq!(bytes[GUID_CHARS * 2 + 1], 0);
+    }
+
+    #[test]
+    fn test_from_bytes_rejects_wrong_length() {
+        let bytes = [0u8; CONTAINER_RECORD_SIZE - 1];
+        assert!(ContainerRecord::from_bytes(&bytes).is_err());
+    }
+}
@@ -0,0 +1,348 @@
+//! Microsoft Enterprise Root Certificate Store (`msroots`) Support
+//!
+//! `msroots` is one of the PIV data objects the Windows minidriver reads on
+//! enumeration: a PKCS#7 `SignedData` structure with no content and no
+//! signatures, its `certificates` field doing all the work. Rather than
+//! grow a single object without bound, the card spreads the blob across a
+//! sequential run of objects, which is why [`MsRoots::read`] and
+//! [`MsRoots::write`] spend as much code stitching chunks together as they
+//! do on the DER itself.
+
+use crate::{certificate::Certificate, error::Error, yubikey::YubiKey};
+
+/// First `msroots` data object ID.
+const OBJ_MSROOTS_BASE: u32 = 0x005f_ff01;
+
+/// Last `msroots` data object ID. The minidriver spec allows for up to five
+/// chunks; a card which needs more than this cannot be supported.
+const OBJ_MSROOTS_END: u32 = 0x005f_ff05;
+
+/// OID for PKCS#7 `signedData` (1.2.840.113549.1.7.2)
+const OID_SIGNED_DATA: &[u8] = &[0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x07, 0x02];
+
+/// OID for PKCS#7 `data` (1.2.840.113549.1.7.1), used as the (empty)
+/// `encapContentInfo` content type.
+const OID_DATA: &[u8] = &[0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x07, 0x01];
+
+/// Maximum number of bytes that fit in a single `msroots` data object
+const CHUNK_CAPACITY: usize = 3072;
+
+/// DER universal tags used while walking the `SignedData` structure.
+const TAG_SEQUENCE: u8 = 0x30;
+const TAG_SET: u8 = 0x31;
+const TAG_OID: u8 = 0x06;
+const TAG_INTEGER: u8 = 0x02;
+const TAG_CONTEXT_0: u8 = 0xa0;
+
+/// Microsoft Enterprise Root Certificate Store
+///
+/// Handles reading and writing the `msroots` PIV data object, which holds a
+/// degenerate (contentless, signature-less) PKCS#7 `SignedData` blob wrapping
+/// the certificates the Windows minidriver should trust.
+pub struct MsRoots;
+
+impl MsRoots {
+    /// Read the enterprise root truststore off the YubiKey
+    pub fn read(yubikey: &mut YubiKey) -> Result<Vec<Certificate>, Error> {
+        let mut blob = Vec::new();
+        let txn = yubikey.begin_transaction()?;
+
+        for obj_id in OBJ_MSROOTS_BASE..=OBJ_MSROOTS_END {
+            let chunk = match txn.fetch_object(obj_id) {
+                Ok(chunk) => chunk,
+                // A missing chunk object is how the sequence naturally ends
+                // (the card doesn't pre-allocate all five); anything else is
+                // a real transaction failure and must not be swallowed.
+                Err(Error::ObjectNotFound) => break,
+                Err(e) => return Err(e),
+            };
+
+            if chunk.is_empty() {
+                break;
+            }
+
+            let short = chunk.len() < CHUNK_CAPACITY;
+            blob.extend_from_slice(&chunk);
+
+            if short {
+                break;
+            }
+        }
+
+        parse_signed_data(&blob)
+    }
+
+    /// Write a new enterprise root truststore to the YubiKey, replacing
+    /// whatever `msroots` chunks are currently present
+    #[cfg(feature = "untested")]
+    pub fn write(yubikey: &mut YubiKey, certs: &[Certificate]) -> Result<(), Error> {
+        let blob = build_signed_data(certs);
+        let txn = yubikey.begin_transaction()?;
+
+        let mut obj_id = OBJ_MSROOTS_BASE;
+        for chunk in blob.chunks(CHUNK_CAPACITY) {
+            if obj_id > OBJ_MSROOTS_END {
+                return Err(Error::GenericError);
+            }
+
+            txn.save_object(obj_id, chunk)?;
+            obj_id += 1;
+        }
+
+        // A short (possibly empty) final chunk terminates the sequence for
+        // readers. If the last chunk written is full, write an empty one after
+        // it so a chunk left over from a previous, larger blob isn't read as a
+        // continuation; chunks past the terminator are left in place.
+        if blob.len() % CHUNK_CAPACITY == 0 && obj_id <= OBJ_MSROOTS_END {
+            txn.save_object(obj_id, &[])?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Read a DER tag + length-prefixed value, returning `(tag, content, rest)`
+fn read_tlv(buf: &[u8]) -> Result<(u8, &[u8], &[u8]), Error> {
+    let &tag = buf.first().ok_or(Error::GenericError)?;
+    let len_byte = *buf.get(1).ok_or(Error::GenericError)?;
+
+    let (len, header_len) = if len_byte & 0x80 == 0 {
+        (len_byte as usize, 2)
+    } else {
+        let n_bytes = (len_byte & 0x7f) as usize;
+        if n_bytes == 0 || n_bytes > 4 {
+            return Err(Error::GenericError);
+        }
+
+        let len_bytes = buf.get(2..2 + n_bytes).ok_or(Error::GenericError)?;
+        let mut len = 0usize;
+        for &b in len_bytes {
+            len = (len << 8) | b as usize;
+        }
+
+        (len, 2 + n_bytes)
+    };
+
+    let content = buf
+        .get(header_len..header_len + len)
+        .ok_or(Error::GenericError)?;
+    let rest = &buf[header_len + len..];
+    Ok((tag, content, rest))
+}
+
+/// Encode a DER tag + length-prefixed value
+fn write_tlv(tag: u8, content: &[u8], out: &mut Vec<u8>) {
+    out.push(tag);
+    write_der_len(content.len(), out);
+    out.extend_from_slice(content);
+}
+
+/// Encode a DER length
+fn write_der_len(len: usize, out: &mut Vec<u8>) {
+    if len < 0x80 {
+        out.push(len as u8);
+        return;
+    }
+
+    let bytes = len.to_be_bytes();
+    let significant: Vec<u8> = bytes
+        .iter()
+        .copied()
+        .skip_while(|&b| b == 0)
+        .collect();
+    out.push(0x80 | significant.len() as u8);
+    out.extend_from_slice(&significant);
+}
+
+/// Parse the degenerate PKCS#7 `SignedData` blob and return its certificates
+fn parse_signed_data(blob: &[u8]) -> Result<Vec<Certificate>, Error> {
+    // ContentInfo ::= SEQUENCE { contentType OID, content [0] EXPLICIT SignedData }
+    let (tag, content_info, _) = read_tlv(blob)?;
+    if tag != TAG_SEQUENCE {
+        return Err(Error::GenericError);
+    }
+
+    let (tag, oid, rest) = read_tlv(content_info)?;
+    if tag != TAG_OID || oid != OID_SIGNED_DATA {
+        return Err(Error::GenericError);
+    }
+
+    let (tag, signed_data, _) = read_tlv(rest)?;
+    if tag != TAG_CONTEXT_0 {
+        return Err(Error::GenericError);
+    }
+
+    // SignedData ::= SEQUENCE { version, digestAlgorithms, encapContentInfo,
+    //                           certificates [0] IMPLICIT SET OF Certificate OPTIONAL, ... }
+    let (tag, signed_data, _) = read_tlv(signed_data)?;
+    if tag != TAG_SEQUENCE {
+        return Err(Error::GenericError);
+    }
+
+    let (tag, _version, rest) = read_tlv(signed_data)?;
+    if tag != TAG_INTEGER {
+        return Err(Error::GenericError);
+    }
+
+    let (tag, _digest_algs, rest) = read_tlv(rest)?;
+    if tag != TAG_SET {
+        return Err(Error::GenericError);
+    }
+
+    let (tag, _encap_content_info, mut rest) = read_tlv(rest)?;
+    if tag != TAG_SEQUENCE {
+        return Err(Error::GenericError);
+    }
+
+    let mut certs = Vec::new();
+    if let Ok((tag, cert_set, _)) = read_tlv(rest) {
+        if tag == TAG_CONTEXT_0 {
+            let mut certs_buf = cert_set;
+            while !certs_buf.is_empty() {
+                let (cert_tag, cert_body, cert_rest) = read_tlv(certs_buf)?;
+                if cert_tag != TAG_SEQUENCE {
+                    return Err(Error::GenericError);
+                }
+
+                let mut der = Vec::with_capacity(cert_body.len() + 4);
+                write_tlv(TAG_SEQUENCE, cert_body, &mut der);
+                certs.push(Certificate::from_bytes(der)?);
+                certs_buf = cert_rest;
+            }
+
+            rest = &[];
+        }
+    }
+
+    let _ = rest;
+    Ok(certs)
+}
+
+/// Build a degenerate PKCS#7 `SignedData` blob wrapping `certs`
+#[cfg(feature = "untested")]
+fn build_signed_data(certs: &[Certificate]) -> Vec<u8> {
+    let mut cert_set = Vec::new();
+    for cert in certs {
+        cert_set.extend_from_slice(cert.as_ref());
+    }
+
+    let mut certificates = Vec::new();
+    write_tlv(TAG_CONTEXT_0, &cert_set, &mut certificates);
+
+    let mut encap_content_info = Vec::new();
+    write_tlv(TAG_OID, OID_DATA, &mut encap_content_info);
+    let mut encap_content_info_seq = Vec::new();
+    write_tlv(TAG_SEQUENCE, &encap_content_info, &mut encap_content_info_seq);
+
+    let mut signed_data = Vec::new();
+    write_tlv(TAG_INTEGER, &[0x01], &mut signed_data); // version 1
+    write_tlv(TAG_SET, &[], &mut signed_data); // digestAlgorithms: empty
+    signed_data.extend_from_slice(&encap_content_info_seq);
+    signed_data.extend_from_slice(&certificates);
+    write_tlv(TAG_SET, &[], &mut signed_data); // signerInfos: empty
+
+    let mut signed_data_seq = Vec::new();
+    write_tlv(TAG_SEQUENCE, &signed_data, &mut signed_data_seq);
+
+    let mut content = Vec::new();
+    write_tlv(TAG_CONTEXT_0, &signed_data_seq, &mut content);
+
+    let mut content_info = Vec::new();
+    write_tlv(TAG_OID, OID_SIGNED_DATA, &mut content_info);
+    content_info.extend_from_slice(&content);
+
+    let mut out = Vec::new();
+    write_tlv(TAG_SEQUENCE, &content_info, &mut out);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_tlv_short_form_length() {
+        let (tag, content, rest) = read_tlv(&[0x04, 0x03, 0xaa, 0xbb, 0xcc, 0xff]).unwrap();
+        assert_eq!(tag, 0x04);
+        assert_eq!(content, &[0xaa, 0xbb, 0xcc]);
+        assert_eq!(rest, &[0xff]);
+    }
+
+    #[test]
+    fn test_read_tlv_long_form_length() {
+        let mut buf = vec![0x04, 0x82, 0x01, 0x00];
+        buf.extend(std::iter::repeat(0xaa).take(256));
+        let (tag, content, rest) = read_tlv(&buf).unwrap();
+        assert_eq!(tag, 0x04);
+        assert_eq!(content.len(), 256);
+        assert!(rest.is_empty());
+    }
+
+    #[test]
+    fn test_read_tlv_rejects_truncated_header() {
+        assert!(read_tlv(&[0x04]).is_err());
+    }
+
+    #[test]
+    fn test_read_tlv_rejects_truncated_content() {
+        // Length byte claims 5 bytes of content, but only 2 are present.
+        assert!(read_tlv(&[0x04, 0x05, 0xaa, 0xbb]).is_err());
+    }
+
+    #[test]
+    fn test_write_tlv_round_trips_through_read_tlv() {
+        let mut buf = Vec::new();
+        write_tlv(TAG_OID, OID_SIGNED_DATA, &mut buf);
+
+        let (tag, content, rest) = read_tlv(&buf).unwrap();
+        assert_eq!(tag, TAG_OID);
+        assert_eq!(content, OID_SIGNED_DATA);
+        assert!(rest.is_empty());
+    }
+
+    #[test]
+    fn test_write_der_len_round_trips_long_form() {
+        let mut buf = Vec::new();
+        write_der_len(300, &mut buf);
+        buf.extend(std::iter::repeat(0).take(300));
+
+        let (_, content, _) = read_tlv(&{
+            let mut with_tag = vec![TAG_SEQUENCE];
+            with_tag.extend_from_slice(&buf);
+            with_tag
+        })
+        .unwrap();
+        assert_eq!(content.len(), 300);
+    }
+
+    #[test]
+    #[cfg(feature = "untested")]
+    fn test_parse_signed_data_round_trips_empty_cert_list() {
+        let blob = build_signed_data(&[]);
+        assert_eq!(parse_signed_data(&blob).unwrap(), Vec::new());
+    }
+
+    #[test]
+    #[cfg(feature = "untested")]
+    fn test_parse_signed_data_rejects_truncated_blob() {
+        let blob = build_signed_data(&[]);
+        assert!(parse_signed_data(&blob[..blob.len() - 1]).is_err());
+    }
+
+    #[test]
+    fn test_parse_signed_data_rejects_wrong_content_type_oid() {
+        let mut content_info = Vec::new();
+        write_tlv(TAG_OID, OID_DATA, &mut content_info); // not OID_SIGNED_DATA
+        write_tlv(TAG_CONTEXT_0, &[], &mut content_info);
+
+        let mut blob = Vec::new();
+        write_tlv(TAG_SEQUENCE, &content_info, &mut blob);
+
+        assert!(parse_signed_data(&blob).is_err());
+    }
+
+    #[test]
+    fn test_parse_signed_data_rejects_non_sequence_top_level_tag() {
+        assert!(parse_signed_data(&[TAG_SET, 0x00]).is_err());
+    }
+}
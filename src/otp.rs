@@ -0,0 +1,226 @@
+//! Yubico OTP Application Support
+//!
+//! Unlike `cccid`/`mscmap`/`msroots`, which all read and write PIV data
+//! objects under whatever applet a transaction already has selected, the
+//! two programmable OTP slots live behind their own smart-card application.
+//! Every `CONFIG` write here first re-selects [`OTP_AID`] so the instruction
+//! actually lands on the OTP applet rather than whatever was selected
+//! beforehand; the ModHex helpers below only decode/encode the public-ID
+//! prefix those slots emit and don't touch the card at all.
+
+use crate::{error::Error, modhex, yubikey::YubiKey};
+
+/// `CLA` byte used by the OTP application's configuration APDU
+const CLA_OTP: u8 = 0x00;
+
+/// `INS` for `SELECT` (ISO/IEC 7816-4)
+const INS_SELECT: u8 = 0xa4;
+
+/// `P1` for `SELECT` by application AID (ISO/IEC 7816-4)
+const SELECT_P1_AID: u8 = 0x04;
+
+/// AID of the YubiKey OTP application
+const OTP_AID: &[u8] = &[0xa0, 0x00, 0x00, 0x05, 0x27, 0x20, 0x01];
+
+/// `CONFIG` instruction targeting slot 1
+const INS_CONFIG_1: u8 = 0x01;
+
+/// `CONFIG` instruction targeting slot 2
+const INS_CONFIG_2: u8 = 0x03;
+
+/// Length in bytes of the AES key used by the OTP application
+pub const AES_KEY_SIZE: usize = 16;
+
+/// Length in bytes of the per-device private ID embedded in each OTP
+pub const PRIVATE_ID_SIZE: usize = 6;
+
+/// Length in bytes of the public ID, i.e. the ModHex-encoded device
+/// identifier that prefixes every OTP
+pub const PUBLIC_ID_SIZE: usize = 6;
+
+/// Length in bytes of the (optional) access code gating reprogramming
+pub const ACCESS_CODE_SIZE: usize = 6;
+
+/// Length in bytes of the `fixed` field of the on-wire `config_st`
+///
+/// Only the first [`PUBLIC_ID_SIZE`] bytes are meaningful; the rest is
+/// zero padding, with `fixedSize` telling the firmware how much to use.
+const FIXED_FIELD_SIZE: usize = 16;
+
+/// Size in bytes of the on-wire `config_st` sent in the `CONFIG` APDU:
+/// `fixed + uid + key + accCode + fixedSize + extFlags + tktFlags + cfgFlags
+/// + rfu + crc`
+const CONFIG_SIZE: usize =
+    FIXED_FIELD_SIZE + PRIVATE_ID_SIZE + AES_KEY_SIZE + ACCESS_CODE_SIZE + 1 + 1 + 1 + 1 + 2 + 2;
+
+/// Feedback polynomial (reflected) of the CRC16 the firmware checks every
+/// `config_st` against before accepting a configuration write
+const CRC16_POLY: u16 = 0x8408;
+
+/// Which of the two OTP configuration slots to target
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum OtpSlot {
+    /// Slot 1, conventionally triggered by a short touch
+    Short,
+    /// Slot 2, conventionally triggered by a long touch
+    Long,
+}
+
+impl OtpSlot {
+    /// The `CONFIG` instruction byte for this slot
+    fn instruction(self) -> u8 {
+        match self {
+            OtpSlot::Short => INS_CONFIG_1,
+            OtpSlot::Long => INS_CONFIG_2,
+        }
+    }
+}
+
+/// Configuration for a single OTP slot
+///
+/// Built up with [`OtpConfig::new`] and written with [`OtpConfig::write`].
+#[derive(Clone)]
+pub struct OtpConfig {
+    fixed: [u8; PUBLIC_ID_SIZE],
+    uid: [u8; PRIVATE_ID_SIZE],
+    key: [u8; AES_KEY_SIZE],
+    access_code: [u8; ACCESS_CODE_SIZE],
+}
+
+impl OtpConfig {
+    /// Start building a slot configuration from its public ID, private ID,
+    /// and AES key
+    pub fn new(
+        public_id: [u8; PUBLIC_ID_SIZE],
+        private_id: [u8; PRIVATE_ID_SIZE],
+        key: [u8; AES_KEY_SIZE],
+    ) -> Self {
+        Self {
+            fixed: public_id,
+            uid: private_id,
+            key,
+            access_code: [0u8; ACCESS_CODE_SIZE],
+        }
+    }
+
+    /// Require `access_code` to reprogram this slot in the future
+    pub fn with_access_code(mut self, access_code: [u8; ACCESS_CODE_SIZE]) -> Self {
+        self.access_code = access_code;
+        self
+    }
+
+    /// Serialize this configuration to the on-wire `config_st` structure,
+    /// including the trailing CRC16 the firmware verifies before accepting
+    /// the write
+    fn to_bytes(&self) -> [u8; CONFIG_SIZE] {
+        let mut buf = [0u8; CONFIG_SIZE];
+
+        let mut off = 0;
+        buf[off..off + PUBLIC_ID_SIZE].copy_from_slice(&self.fixed);
+        off += FIXED_FIELD_SIZE;
+        buf[off..off + PRIVATE_ID_SIZE].copy_from_slice(&self.uid);
+        off += PRIVATE_ID_SIZE;
+        buf[off..off + AES_KEY_SIZE].copy_from_slice(&self.key);
+        off += AES_KEY_SIZE;
+        buf[off..off + ACCESS_CODE_SIZE].copy_from_slice(&self.access_code);
+        off += ACCESS_CODE_SIZE;
+
+        buf[off] = PUBLIC_ID_SIZE as u8; // fixedSize
+        off += 1;
+        buf[off] = 0; // extFlags
+        off += 1;
+        buf[off] = 0; // tktFlags
+        off += 1;
+        buf[off] = 0; // cfgFlags
+        off += 1;
+        off += 2; // rfu, left zeroed
+
+        let crc = !crc16(&buf[..off]);
+        buf[off..off + 2].copy_from_slice(&crc.to_le_bytes());
+
+        buf
+    }
+
+    /// Write this configuration to the given OTP slot
+    ///
+    /// Selects the OTP application before issuing `CONFIG`, since the
+    /// transaction may currently have the PIV applet selected instead.
+    #[cfg(feature = "untested")]
+    pub fn write(&self, yubikey: &mut YubiKey, slot: OtpSlot) -> Result<(), Error> {
+        let txn = yubikey.begin_transaction()?;
+        txn.transfer_data(CLA_OTP, INS_SELECT, SELECT_P1_AID, 0, OTP_AID)?;
+        txn.transfer_data(CLA_OTP, slot.instruction(), 0, 0, &self.to_bytes())?;
+        Ok(())
+    }
+}
+
+/// CRC16 checksum used by the OTP application's firmware to validate a
+/// `config_st` before accepting it
+///
+/// The stored checksum is the one's complement of this function's output
+/// over the structure up to (but not including) the checksum field itself.
+fn crc16(data: &[u8]) -> u16 {
+    let mut crc: u16 = 0xffff;
+
+    for &byte in data {
+        crc ^= u16::from(byte);
+        for _ in 0..8 {
+            if crc & 1 != 0 {
+                crc = (crc >> 1) ^ CRC16_POLY;
+            } else {
+                crc >>= 1;
+            }
+        }
+    }
+
+    crc
+}
+
+/// Format a raw OTP public ID as its ModHex string
+pub fn format_public_id(public_id: &[u8; PUBLIC_ID_SIZE]) -> String {
+    modhex::encode(public_id)
+}
+
+/// Parse a ModHex-encoded OTP public ID
+pub fn parse_public_id(s: &str) -> Result<[u8; PUBLIC_ID_SIZE], Error> {
+    let decoded = modhex::decode(s)?;
+
+    if decoded.len() != PUBLIC_ID_SIZE {
+        return Err(Error::GenericError);
+    }
+
+    let mut id = [0u8; PUBLIC_ID_SIZE];
+    id.copy_from_slice(&decoded);
+    Ok(id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_public_id_round_trip() {
+        let id = [0x01, 0x02, 0x03, 0x04, 0x05, 0x06];
+        let formatted = format_public_id(&id);
+        assert_eq!(parse_public_id(&formatted).unwrap(), id);
+    }
+
+    #[test]
+    fn test_parse_public_id_rejects_wrong_length() {
+        assert!(parse_public_id("cb").is_err());
+    }
+
+    #[test]
+    fn test_to_bytes_layout_and_crc() {
+        let config = OtpConfig::new([1; PUBLIC_ID_SIZE], [2; PRIVATE_ID_SIZE], [3; AES_KEY_SIZE]);
+        let bytes = config.to_bytes();
+
+        assert_eq!(bytes.len(), CONFIG_SIZE);
+        assert_eq!(&bytes[0..PUBLIC_ID_SIZE], &[1; PUBLIC_ID_SIZE]);
+        assert_eq!(&bytes[PUBLIC_ID_SIZE..FIXED_FIELD_SIZE], &[0; FIXED_FIELD_SIZE - PUBLIC_ID_SIZE]);
+        assert_eq!(bytes[FIXED_FIELD_SIZE + PRIVATE_ID_SIZE + AES_KEY_SIZE + ACCESS_CODE_SIZE], PUBLIC_ID_SIZE as u8);
+
+        let crc_offset = CONFIG_SIZE - 2;
+        assert_eq!(!crc16(&bytes[..crc_offset]), u16::from_le_bytes([bytes[crc_offset], bytes[crc_offset + 1]]));
+    }
+}